@@ -9,7 +9,12 @@
 // The threshold_ms value can be adjusted based on how sensitive you want the detection to be.
 
 
-use std::time::Instant;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
 
 fn is_being_debugged(threshold_ms: u128) -> bool {
     let start_time = Instant::now();
@@ -28,6 +33,243 @@ fn is_being_debugged(threshold_ms: u128) -> bool {
     false
 }
 
+// Explanation:
+// Sleep-acceleration Anti-Debugging:
+
+// The check above only catches an environment that makes the routine run *slowly*.
+// Automated sandboxes often do the opposite: they patch Sleep/nanosleep so that a
+// long idle returns instantly, letting malware that waits out analysis run straight
+// through. Here we record Instant::now(), sleep for the requested duration, then
+// verify that at least that much time actually elapsed (minus a small tolerance).
+// A faithful monotonic clock never returns early, so a shortfall is strong evidence
+// the sleep was patched by an evasion-resistant analysis environment.
+
+fn is_sleep_accelerated(requested: Duration) -> bool {
+    // Tolerance for scheduler jitter; a patched sleep undershoots by far more.
+    let tolerance = Duration::from_millis(2);
+
+    let start_time = Instant::now();
+    std::thread::sleep(requested);
+    let elapsed = start_time.elapsed();
+
+    if elapsed + tolerance < requested {
+        println!(
+            "Sleep returned early: asked for {} ms, measured {} ms, possibly an accelerated sandbox.",
+            requested.as_millis(),
+            elapsed.as_millis()
+        );
+        return true;
+    }
+    false
+}
+
+// Explanation:
+// Non-blocking async Anti-Debugging:
+
+// is_being_debugged relies on std::thread::sleep, which parks the whole thread and is
+// conspicuous under analysis. This async variant awaits smol's Timer::after instead, so
+// the probe yields to the executor and runs concurrently with real work. We still bracket
+// the await with Instant::now()/elapsed() and apply the same threshold verdict, letting
+// the check be polled repeatedly without dedicating a thread — important when detection
+// is embedded inside an already-async application.
+
+async fn is_being_debugged_async(threshold: Duration) -> bool {
+    let start_time = Instant::now();
+
+    // Yield to the executor for a short fixed interval instead of blocking the thread.
+    smol::Timer::after(Duration::from_millis(10)).await;
+
+    let elapsed = start_time.elapsed();
+    if elapsed > threshold {
+        println!(
+            "Routine took too long to execute: {} ms, possibly being debugged.",
+            elapsed.as_millis()
+        );
+        return true;
+    }
+    false
+}
+
+// Explanation:
+// Clock-skew Anti-Debugging:
+
+// Debuggers and sandbox harnesses often freeze or fast-forward the wall clock while
+// leaving the monotonic clock alone (or vice versa). We sample both Instant::now()
+// and SystemTime::now() around a fixed-duration busy loop and compare the two deltas.
+// Normally they agree within a few milliseconds; a large divergence betrays that one
+// clock is being manipulated. The returned value is the signed millisecond difference
+// (monotonic minus wall) so the caller can see both the magnitude and direction of the
+// skew. A backward wall-clock jump makes SystemTime::duration_since fail, which is
+// itself a tampering signal, so we count it as a negative wall-clock delta rather than
+// panicking; the returned skew (monotonic minus wall) is then large and positive.
+
+fn detect_clock_skew(duration: Duration) -> Option<i128> {
+    let mono_start = Instant::now();
+    let wall_start = SystemTime::now();
+
+    // Busy-wait for the requested duration rather than sleeping, so both clocks are
+    // observed advancing under the same workload.
+    while mono_start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+
+    let mono_delta = mono_start.elapsed().as_millis() as i128;
+    let wall_end = SystemTime::now();
+
+    // A forward wall-clock step gives a positive duration; a backward jump is surfaced
+    // as a negative delta instead of an error.
+    let wall_delta = match wall_end.duration_since(wall_start) {
+        Ok(elapsed) => elapsed.as_millis() as i128,
+        Err(err) => -(err.duration().as_millis() as i128),
+    };
+
+    Some(mono_delta - wall_delta)
+}
+
+// Explanation:
+// Continuous interval-based Anti-Debugging:
+
+// A single one-shot check is trivially bypassed by attaching the debugger after startup.
+// DebugMonitor spawns a background task that re-runs the timing probe on a fixed interval
+// (smol's Timer::interval yields once per tick) and invokes a user-supplied FnMut(bool)
+// callback with each verdict, so the host app learns the moment timing anomalies appear
+// mid-execution. start() returns a handle whose stop() joins the background task, turning
+// the crate's point-in-time trick into a persistent watchdog.
+
+struct DebugMonitor {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DebugMonitor {
+    fn start<F>(interval: Duration, threshold: Duration, mut cb: F) -> DebugMonitor
+    where
+        F: FnMut(bool) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let task_running = Arc::clone(&running);
+        let threshold_ms = threshold.as_millis();
+
+        let handle = std::thread::spawn(move || {
+            use smol::stream::StreamExt;
+            smol::block_on(async move {
+                let mut ticker = smol::Timer::interval(interval);
+                while task_running.load(Ordering::Relaxed) {
+                    ticker.next().await;
+                    if !task_running.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    cb(is_being_debugged(threshold_ms));
+                }
+            });
+        });
+
+        DebugMonitor {
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    fn stop(mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Explanation:
+// Exclusive-time stopwatch for threshold calibration:
+
+// Hard-coding threshold_ms = 5000 is fragile across machines. To derive thresholds
+// dynamically we first need a trustworthy baseline for the probed routine, and a naive
+// Instant-start/elapsed pair double-counts time whenever an instrumented function calls
+// another instrumented function. Stopwatch keeps a thread-local stack of frames, each
+// holding (section_id, start, child_accumulated). On enter we push a frame; on exit we
+// compute total = start.elapsed(), exclusive = total - child_accumulated, fold total into
+// the parent frame's child_accumulated, and record the exclusive time against the section.
+// This yields both inclusive (total) and exclusive durations even when bar() calls foo(),
+// giving a reliable noise-floor measurement for anti-debug thresholding.
+
+struct Frame {
+    section_id: &'static str,
+    start: Instant,
+    child_accumulated: Duration,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Section {
+    inclusive: Duration,
+    exclusive: Duration,
+    calls: u64,
+}
+
+thread_local! {
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+    static SECTIONS: RefCell<HashMap<&'static str, Section>> = RefCell::new(HashMap::new());
+}
+
+struct Stopwatch;
+
+// RAII guard: drop marks the end of the instrumented section, mirroring how the rest of
+// the crate brackets a routine with Instant::now()/elapsed().
+struct StopwatchGuard {
+    section_id: &'static str,
+}
+
+impl Stopwatch {
+    fn enter(section_id: &'static str) -> StopwatchGuard {
+        STACK.with(|stack| {
+            stack.borrow_mut().push(Frame {
+                section_id,
+                start: Instant::now(),
+                child_accumulated: Duration::ZERO,
+            });
+        });
+        StopwatchGuard { section_id }
+    }
+
+    // Inclusive and exclusive totals accumulated so far for a section, if any.
+    fn section(section_id: &'static str) -> Option<(Duration, Duration)> {
+        SECTIONS.with(|sections| {
+            sections
+                .borrow()
+                .get(section_id)
+                .map(|s| (s.inclusive, s.exclusive))
+        })
+    }
+}
+
+impl Drop for StopwatchGuard {
+    fn drop(&mut self) {
+        let frame = STACK.with(|stack| stack.borrow_mut().pop());
+        let frame = match frame {
+            Some(frame) => frame,
+            None => return,
+        };
+        debug_assert_eq!(frame.section_id, self.section_id, "Stopwatch frames out of order");
+
+        let total = frame.start.elapsed();
+        let exclusive = total.saturating_sub(frame.child_accumulated);
+
+        // Charge our full inclusive time to the parent's children so the parent's own
+        // exclusive time excludes it.
+        STACK.with(|stack| {
+            if let Some(parent) = stack.borrow_mut().last_mut() {
+                parent.child_accumulated += total;
+            }
+        });
+
+        SECTIONS.with(|sections| {
+            let mut sections = sections.borrow_mut();
+            let entry = sections.entry(frame.section_id).or_default();
+            entry.inclusive += total;
+            entry.exclusive += exclusive;
+            entry.calls += 1;
+        });
+    }
+}
+
 fn main() {
     let threshold_ms = 5000; // Set your threshold
     if is_being_debugged(threshold_ms) {
@@ -35,4 +277,54 @@ fn main() {
     } else {
         println!("No debugging detected.");
     }
+
+    if is_sleep_accelerated(Duration::from_millis(100)) {
+        println!("Sleep acceleration detected!");
+    } else {
+        println!("No sleep acceleration detected.");
+    }
+
+    if let Some(skew_ms) = detect_clock_skew(Duration::from_millis(100)) {
+        if skew_ms.abs() > 50 {
+            println!("Clock skew detected: {} ms between clocks.", skew_ms);
+        } else {
+            println!("Clocks agree (skew {} ms).", skew_ms);
+        }
+    }
+
+    if smol::block_on(is_being_debugged_async(Duration::from_millis(5000))) {
+        println!("Debugging detected (async)!");
+    } else {
+        println!("No debugging detected (async).");
+    }
+
+    let monitor = DebugMonitor::start(
+        Duration::from_secs(1),
+        Duration::from_millis(5000),
+        |debugged| {
+            if debugged {
+                println!("Watchdog: timing anomaly detected!");
+            }
+        },
+    );
+    std::thread::sleep(Duration::from_secs(3));
+    monitor.stop();
+
+    // Measure and print a baseline to calibrate a threshold from. `bar` is instrumented and itself
+    // calls the instrumented `foo`, so exclusive time must not double-count `foo`.
+    {
+        let _bar = Stopwatch::enter("bar");
+        std::thread::sleep(Duration::from_millis(20));
+        {
+            let _foo = Stopwatch::enter("foo");
+            std::thread::sleep(Duration::from_millis(30));
+        }
+    }
+    if let Some((inclusive, exclusive)) = Stopwatch::section("bar") {
+        println!(
+            "Baseline for `bar`: inclusive {} ms, exclusive {} ms.",
+            inclusive.as_millis(),
+            exclusive.as_millis()
+        );
+    }
 }